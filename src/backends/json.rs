@@ -0,0 +1,191 @@
+/**
+ * src/backends/json.rs
+ */
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::repository::{creates_cycle, Repository};
+use crate::task::{now_unix, validate_description, validate_tags, NewTask, Task, TaskError, TaskUpdate};
+
+/// JSON-file-backed implementation of [`Repository`].
+///
+/// Loads the whole task list into memory on open and rewrites the file after
+/// every mutation, matching the tool's original `tasks.json` behavior.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct JsonRepository {
+    tasks: Vec<Task>,
+    next_id: usize,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl JsonRepository {
+    /// Open (or create) the JSON task file at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, TaskError> {
+        let path = path.as_ref().to_path_buf();
+        if path.exists() {
+            let contents =
+                fs::read_to_string(&path).map_err(|e| TaskError::FileError(e.to_string()))?;
+            let mut repo: JsonRepository =
+                serde_json::from_str(&contents).map_err(|e| TaskError::FileError(e.to_string()))?;
+            repo.path = path;
+            Ok(repo)
+        } else {
+            Ok(JsonRepository {
+                tasks: Vec::new(),
+                next_id: 1,
+                path,
+            })
+        }
+    }
+
+    fn save(&self) -> Result<(), TaskError> {
+        let json =
+            serde_json::to_string_pretty(self).map_err(|e| TaskError::FileError(e.to_string()))?;
+        fs::write(&self.path, json).map_err(|e| TaskError::FileError(e.to_string()))
+    }
+}
+
+impl Repository for JsonRepository {
+    fn insert_task(&mut self, new_task: NewTask) -> Result<usize, TaskError> {
+        validate_description(&new_task.description)?;
+        validate_tags(&new_task.tags)?;
+
+        for dep in &new_task.depends_on {
+            if !self.tasks.iter().any(|task| task.id == *dep) {
+                return Err(TaskError::TaskNotFound(*dep));
+            }
+        }
+
+        let id = self.next_id;
+        self.tasks.push(Task {
+            id,
+            description: new_task.description,
+            completed: false,
+            started_at: None,
+            duration_secs: 0,
+            depends_on: new_task.depends_on,
+            priority: new_task.priority,
+            link: new_task.link,
+            tags: new_task.tags,
+            command: new_task.command,
+        });
+        self.next_id += 1;
+        self.save()?;
+        Ok(id)
+    }
+
+    fn get_tasks(&self) -> Result<Vec<Task>, TaskError> {
+        Ok(self.tasks.clone())
+    }
+
+    fn complete_task(&mut self, id: usize) -> Result<(), TaskError> {
+        match self.tasks.iter_mut().find(|task| task.id == id) {
+            Some(task) if task.started_at.is_some() => Err(TaskError::TaskIsActive(id)),
+            Some(task) => {
+                task.completed = true;
+                self.save()
+            }
+            None => Err(TaskError::TaskNotFound(id)),
+        }
+    }
+
+    fn remove_task(&mut self, id: usize) -> Result<(), TaskError> {
+        match self.tasks.iter().position(|task| task.id == id) {
+            Some(i) => {
+                self.tasks.remove(i);
+                self.save()
+            }
+            None => Err(TaskError::TaskNotFound(id)),
+        }
+    }
+
+    fn get_current_task_opt(&self) -> Result<Option<Task>, TaskError> {
+        Ok(self
+            .tasks
+            .iter()
+            .find(|task| task.started_at.is_some())
+            .cloned())
+    }
+
+    fn start_task(&mut self, id: usize) -> Result<(), TaskError> {
+        if let Some(active) = self.tasks.iter().find(|task| task.started_at.is_some()) {
+            return Err(TaskError::TaskAlreadyActive(active.id));
+        }
+
+        match self.tasks.iter_mut().find(|task| task.id == id) {
+            Some(task) if task.completed => Err(TaskError::TaskAlreadyCompleted(id)),
+            Some(task) => {
+                task.started_at = Some(now_unix());
+                self.save()
+            }
+            None => Err(TaskError::TaskNotFound(id)),
+        }
+    }
+
+    fn stop_task(&mut self) -> Result<(), TaskError> {
+        match self.tasks.iter_mut().find(|task| task.started_at.is_some()) {
+            Some(task) => {
+                let started_at = task.started_at.take().expect("checked by find above");
+                let elapsed = (now_unix() - started_at).max(0) as u64;
+                task.duration_secs += elapsed;
+                self.save()
+            }
+            None => Err(TaskError::NoActiveTask),
+        }
+    }
+
+    fn set_dependency(&mut self, id: usize, depends_on: usize) -> Result<(), TaskError> {
+        if !self.tasks.iter().any(|task| task.id == id) {
+            return Err(TaskError::TaskNotFound(id));
+        }
+        if !self.tasks.iter().any(|task| task.id == depends_on) {
+            return Err(TaskError::TaskNotFound(depends_on));
+        }
+        if creates_cycle(&self.tasks, id, depends_on) {
+            return Err(TaskError::DependencyCycle(vec![id, depends_on]));
+        }
+
+        let task = self
+            .tasks
+            .iter_mut()
+            .find(|task| task.id == id)
+            .expect("checked above");
+        if task.depends_on.contains(&depends_on) {
+            return Ok(());
+        }
+        task.depends_on.push(depends_on);
+        self.save()
+    }
+
+    fn edit_task(&mut self, id: usize, update: TaskUpdate) -> Result<(), TaskError> {
+        if let Some(description) = &update.description {
+            validate_description(description)?;
+        }
+        if let Some(tags) = &update.tags {
+            validate_tags(tags)?;
+        }
+
+        match self.tasks.iter_mut().find(|task| task.id == id) {
+            Some(task) if task.started_at.is_some() => Err(TaskError::TaskIsActive(id)),
+            Some(task) => {
+                if let Some(description) = update.description {
+                    task.description = description;
+                }
+                if let Some(priority) = update.priority {
+                    task.priority = priority;
+                }
+                if let Some(link) = update.link {
+                    task.link = link;
+                }
+                if let Some(tags) = update.tags {
+                    task.tags = tags;
+                }
+                self.save()
+            }
+            None => Err(TaskError::TaskNotFound(id)),
+        }
+    }
+}
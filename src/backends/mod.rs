@@ -0,0 +1,5 @@
+/**
+ * src/backends/mod.rs
+ */
+pub mod json;
+pub mod sqlite;
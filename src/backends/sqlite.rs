@@ -0,0 +1,361 @@
+/**
+ * src/backends/sqlite.rs
+ */
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::repository::{creates_cycle, Repository};
+use crate::task::{now_unix, validate_description, validate_tags, NewTask, Task, TaskError, TaskUpdate};
+
+/// Ordered list of schema migrations. Each entry is raw SQL executed in its
+/// own transaction; applied versions are recorded in `schema_migrations` so
+/// restarts only run what's new.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE tasks (
+        id INTEGER PRIMARY KEY,
+        description TEXT NOT NULL,
+        completed INTEGER NOT NULL DEFAULT 0
+    )",
+    "ALTER TABLE tasks ADD COLUMN started_at INTEGER;
+     ALTER TABLE tasks ADD COLUMN duration_secs INTEGER NOT NULL DEFAULT 0",
+    "CREATE TABLE task_dependencies (
+        task_id INTEGER NOT NULL,
+        depends_on_id INTEGER NOT NULL,
+        PRIMARY KEY (task_id, depends_on_id)
+    )",
+    "ALTER TABLE tasks ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+     ALTER TABLE tasks ADD COLUMN link TEXT;
+     ALTER TABLE tasks ADD COLUMN tags TEXT NOT NULL DEFAULT ''",
+    "ALTER TABLE tasks ADD COLUMN command TEXT",
+];
+
+/// Tags are stored as a JSON array in a single TEXT column, matching how the
+/// JSON backend represents them natively, so a tag containing a comma (or
+/// any other character) round-trips identically on both backends.
+fn tags_to_column(tags: &[String]) -> String {
+    serde_json::to_string(tags).expect("Vec<String> always serializes")
+}
+
+fn column_to_tags(column: &str) -> Vec<String> {
+    if column.is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(column).expect("tags column always holds a JSON array")
+    }
+}
+
+/// SQLite-backed implementation of [`Repository`].
+pub struct SqliteRepository {
+    conn: Connection,
+}
+
+impl SqliteRepository {
+    /// Open (creating if necessary) the SQLite database at `path`, running
+    /// any migrations that haven't been applied yet.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, TaskError> {
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent).map_err(|e| TaskError::FileError(e.to_string()))?;
+        }
+
+        let conn =
+            Connection::open(path.as_ref()).map_err(|e| TaskError::FileError(e.to_string()))?;
+        let repo = SqliteRepository { conn };
+        repo.migrate()?;
+        Ok(repo)
+    }
+
+    fn migrate(&self) -> Result<(), TaskError> {
+        self.conn
+            .execute_batch("CREATE TABLE IF NOT EXISTS schema_migrations (version INTEGER PRIMARY KEY)")
+            .map_err(|e| TaskError::FileError(e.to_string()))?;
+
+        let applied: usize = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM schema_migrations", [], |row| row.get(0))
+            .map_err(|e| TaskError::FileError(e.to_string()))?;
+
+        for (version, migration) in MIGRATIONS.iter().enumerate().skip(applied) {
+            let tx = self
+                .conn
+                .unchecked_transaction()
+                .map_err(|e| TaskError::FileError(e.to_string()))?;
+            tx.execute_batch(migration)
+                .map_err(|e| TaskError::FileError(e.to_string()))?;
+            tx.execute(
+                "INSERT INTO schema_migrations (version) VALUES (?1)",
+                params![version as i64],
+            )
+            .map_err(|e| TaskError::FileError(e.to_string()))?;
+            tx.commit().map_err(|e| TaskError::FileError(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Repository for SqliteRepository {
+    fn insert_task(&mut self, new_task: NewTask) -> Result<usize, TaskError> {
+        validate_description(&new_task.description)?;
+        validate_tags(&new_task.tags)?;
+
+        for dep in &new_task.depends_on {
+            let exists: bool = self
+                .conn
+                .query_row(
+                    "SELECT EXISTS(SELECT 1 FROM tasks WHERE id = ?1)",
+                    params![*dep as i64],
+                    |row| row.get(0),
+                )
+                .map_err(|e| TaskError::FileError(e.to_string()))?;
+            if !exists {
+                return Err(TaskError::TaskNotFound(*dep));
+            }
+        }
+
+        self.conn
+            .execute(
+                "INSERT INTO tasks (description, completed, priority, link, tags, command)
+                 VALUES (?1, 0, ?2, ?3, ?4, ?5)",
+                params![
+                    new_task.description,
+                    new_task.priority as i64,
+                    new_task.link,
+                    tags_to_column(&new_task.tags),
+                    new_task.command
+                ],
+            )
+            .map_err(|e| TaskError::FileError(e.to_string()))?;
+
+        let id = self.conn.last_insert_rowid() as usize;
+
+        for dep in new_task.depends_on {
+            self.conn
+                .execute(
+                    "INSERT INTO task_dependencies (task_id, depends_on_id) VALUES (?1, ?2)",
+                    params![id as i64, dep as i64],
+                )
+                .map_err(|e| TaskError::FileError(e.to_string()))?;
+        }
+
+        Ok(id)
+    }
+
+    fn get_tasks(&self) -> Result<Vec<Task>, TaskError> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, description, completed, started_at, duration_secs, priority, link, tags, command
+                 FROM tasks ORDER BY id",
+            )
+            .map_err(|e| TaskError::FileError(e.to_string()))?;
+
+        let mut tasks: Vec<Task> = stmt
+            .query_map([], |row| {
+                let tags: String = row.get(7)?;
+                Ok(Task {
+                    id: row.get::<_, i64>(0)? as usize,
+                    description: row.get(1)?,
+                    completed: row.get::<_, i64>(2)? != 0,
+                    started_at: row.get(3)?,
+                    duration_secs: row.get::<_, i64>(4)? as u64,
+                    depends_on: Vec::new(),
+                    priority: row.get::<_, i64>(5)? as u8,
+                    link: row.get(6)?,
+                    tags: column_to_tags(&tags),
+                    command: row.get(8)?,
+                })
+            })
+            .map_err(|e| TaskError::FileError(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| TaskError::FileError(e.to_string()))?;
+
+        let mut dep_stmt = self
+            .conn
+            .prepare("SELECT task_id, depends_on_id FROM task_dependencies")
+            .map_err(|e| TaskError::FileError(e.to_string()))?;
+
+        let deps = dep_stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)? as usize, row.get::<_, i64>(1)? as usize))
+            })
+            .map_err(|e| TaskError::FileError(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| TaskError::FileError(e.to_string()))?;
+
+        for (task_id, depends_on_id) in deps {
+            if let Some(task) = tasks.iter_mut().find(|task| task.id == task_id) {
+                task.depends_on.push(depends_on_id);
+            }
+        }
+
+        Ok(tasks)
+    }
+
+    fn complete_task(&mut self, id: usize) -> Result<(), TaskError> {
+        if let Some(active) = self.get_current_task_opt()? {
+            if active.id == id {
+                return Err(TaskError::TaskIsActive(id));
+            }
+        }
+
+        let updated = self
+            .conn
+            .execute("UPDATE tasks SET completed = 1 WHERE id = ?1", params![id as i64])
+            .map_err(|e| TaskError::FileError(e.to_string()))?;
+
+        if updated == 0 {
+            Err(TaskError::TaskNotFound(id))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn remove_task(&mut self, id: usize) -> Result<(), TaskError> {
+        let removed = self
+            .conn
+            .execute("DELETE FROM tasks WHERE id = ?1", params![id as i64])
+            .map_err(|e| TaskError::FileError(e.to_string()))?;
+
+        if removed == 0 {
+            Err(TaskError::TaskNotFound(id))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn get_current_task_opt(&self) -> Result<Option<Task>, TaskError> {
+        self.get_tasks()
+            .map(|tasks| tasks.into_iter().find(|task| task.started_at.is_some()))
+    }
+
+    fn start_task(&mut self, id: usize) -> Result<(), TaskError> {
+        if let Some(active) = self.get_current_task_opt()? {
+            return Err(TaskError::TaskAlreadyActive(active.id));
+        }
+
+        let completed: bool = self
+            .conn
+            .query_row(
+                "SELECT completed FROM tasks WHERE id = ?1",
+                params![id as i64],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()
+            .map_err(|e| TaskError::FileError(e.to_string()))?
+            .map(|completed| completed != 0)
+            .ok_or(TaskError::TaskNotFound(id))?;
+
+        if completed {
+            return Err(TaskError::TaskAlreadyCompleted(id));
+        }
+
+        let updated = self
+            .conn
+            .execute(
+                "UPDATE tasks SET started_at = ?1 WHERE id = ?2",
+                params![now_unix(), id as i64],
+            )
+            .map_err(|e| TaskError::FileError(e.to_string()))?;
+
+        if updated == 0 {
+            Err(TaskError::TaskNotFound(id))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn stop_task(&mut self) -> Result<(), TaskError> {
+        let active = self.get_current_task_opt()?.ok_or(TaskError::NoActiveTask)?;
+        let started_at = active.started_at.expect("active task always has a start time");
+        let elapsed = (now_unix() - started_at).max(0) as u64;
+
+        self.conn
+            .execute(
+                "UPDATE tasks SET started_at = NULL, duration_secs = duration_secs + ?1 WHERE id = ?2",
+                params![elapsed as i64, active.id as i64],
+            )
+            .map_err(|e| TaskError::FileError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    fn set_dependency(&mut self, id: usize, depends_on: usize) -> Result<(), TaskError> {
+        let tasks = self.get_tasks()?;
+        if !tasks.iter().any(|task| task.id == id) {
+            return Err(TaskError::TaskNotFound(id));
+        }
+        if !tasks.iter().any(|task| task.id == depends_on) {
+            return Err(TaskError::TaskNotFound(depends_on));
+        }
+        if creates_cycle(&tasks, id, depends_on) {
+            return Err(TaskError::DependencyCycle(vec![id, depends_on]));
+        }
+        if tasks
+            .iter()
+            .find(|task| task.id == id)
+            .expect("checked above")
+            .depends_on
+            .contains(&depends_on)
+        {
+            return Ok(());
+        }
+
+        self.conn
+            .execute(
+                "INSERT INTO task_dependencies (task_id, depends_on_id) VALUES (?1, ?2)",
+                params![id as i64, depends_on as i64],
+            )
+            .map_err(|e| TaskError::FileError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    fn edit_task(&mut self, id: usize, update: TaskUpdate) -> Result<(), TaskError> {
+        if let Some(description) = &update.description {
+            validate_description(description)?;
+        }
+        if let Some(tags) = &update.tags {
+            validate_tags(tags)?;
+        }
+        if let Some(active) = self.get_current_task_opt()? {
+            if active.id == id {
+                return Err(TaskError::TaskIsActive(id));
+            }
+        }
+
+        let mut task = self
+            .get_tasks()?
+            .into_iter()
+            .find(|task| task.id == id)
+            .ok_or(TaskError::TaskNotFound(id))?;
+
+        if let Some(description) = update.description {
+            task.description = description;
+        }
+        if let Some(priority) = update.priority {
+            task.priority = priority;
+        }
+        if let Some(link) = update.link {
+            task.link = link;
+        }
+        if let Some(tags) = update.tags {
+            task.tags = tags;
+        }
+
+        self.conn
+            .execute(
+                "UPDATE tasks SET description = ?1, priority = ?2, link = ?3, tags = ?4 WHERE id = ?5",
+                params![
+                    task.description,
+                    task.priority as i64,
+                    task.link,
+                    tags_to_column(&task.tags),
+                    id as i64
+                ],
+            )
+            .map_err(|e| TaskError::FileError(e.to_string()))?;
+
+        Ok(())
+    }
+}
@@ -1,12 +1,21 @@
 /**
  * src/main.rs
  */
+mod backends;
+mod repository;
+mod task;
 mod tests;
-use std::fmt;
-use std::fs;
 
-use clap::{Parser, Subcommand};
-use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::{generate, Shell};
+use rayon::prelude::*;
+
+use backends::json::JsonRepository;
+use backends::sqlite::SqliteRepository;
+use repository::{creates_cycle, Repository};
+use task::{NewTask, Task, TaskError, TaskUpdate};
 
 #[derive(Parser)]
 #[command(
@@ -16,299 +25,103 @@ use serde::{Deserialize, Serialize};
     about = "A simple task list / todo CLI application using Clap"
 )]
 struct Cli {
+    /// Storage backend to use
+    #[arg(long, global = true, value_enum, default_value_t = Backend::Json)]
+    backend: Backend,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+enum Backend {
+    Json,
+    Sqlite,
+}
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+enum SortKey {
+    /// By ID, ascending
+    Id,
+    /// By priority, highest first
+    Priority,
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Add a new task to tasks.json
-    Add { task: String },
+    Add {
+        task: String,
+        /// ID of a task that must be completed before this one (repeatable)
+        #[arg(long = "after")]
+        after: Vec<usize>,
+        /// Priority from 0 (lowest) to 3 (highest)
+        #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=3))]
+        priority: u8,
+        /// URL or path associated with the task
+        #[arg(long)]
+        link: Option<String>,
+        /// Tag to attach to the task (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Shell command this task should run instead of holding plain text
+        #[arg(long)]
+        command: Option<String>,
+    },
     /// List all tasks
-    List,
+    List {
+        /// Sort order for the listing
+        #[arg(long, value_enum, default_value_t = SortKey::Id)]
+        sort: SortKey,
+        /// Only show tasks carrying this tag
+        #[arg(long)]
+        tag: Option<String>,
+    },
     /// List only completed tasks
     ListCompleted,
     /// Mark a task as completed, but its ID
     Complete { id: usize },
     /// Remove a task from the task list, by its ID
     Remove { id: usize },
+    /// Edit an existing task's description, priority, link, or tags
+    Edit {
+        id: usize,
+        /// New description
+        #[arg(long)]
+        description: Option<String>,
+        /// New priority from 0 (lowest) to 3 (highest)
+        #[arg(long, value_parser = clap::value_parser!(u8).range(0..=3))]
+        priority: Option<u8>,
+        /// New link
+        #[arg(long)]
+        link: Option<String>,
+        /// Clear the link
+        #[arg(long, conflicts_with = "link")]
+        no_link: bool,
+        /// Replace the task's tags (repeatable); omit to leave tags unchanged
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Clear the task's tags
+        #[arg(long, conflicts_with = "tags")]
+        no_tags: bool,
+        /// ID of a task that must be completed before this one (repeatable)
+        #[arg(long = "after")]
+        after: Vec<usize>,
+    },
+    /// Start tracking time on a task, by its ID
+    Start { id: usize },
+    /// Stop tracking time on the currently active task
+    Stop,
+    /// List incomplete tasks whose dependencies are satisfied, in runnable order
+    Next,
+    /// Generate a shell completion script and print it to stdout
+    Completions { shell: Shell },
+    /// Run all incomplete command-bearing tasks concurrently
+    Run,
     // Note: Doc comments for subcommands must be placed on the corresponding
     // enum variant ^^
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct Task {
-    id: usize,
-    description: String,
-    completed: bool,
-}
-
-#[derive(Debug)]
-enum TaskError {
-    FileError(String),
-    TaskNotFound(usize),
-    InvalidInput(String),
-}
-
-impl fmt::Display for TaskError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            TaskError::FileError(msg) => write!(f, "File Error: {}", msg),
-            TaskError::TaskNotFound(id) => write!(f, "Task with ID {} not found", id),
-            TaskError::InvalidInput(msg) => write!(f, "Invalid Input: {}", msg),
-        }
-    }
-}
-
-impl std::error::Error for TaskError {}
-
-#[derive(Serialize, Deserialize, Debug)]
-/// TaskList struct
-///
-/// # Fields
-///
-/// - `tasks` (`Vec<Task>`) - A vector of task structs
-/// - `next_id` (`usize`) - The next "unique" id in the queue
-///
-/// # Examples
-///
-/// ```
-/// use crate::...;
-///
-/// let s = TaskList {
-///     tasks: value,
-///     next_id: value,
-/// };
-/// ```
-struct TaskList {
-    tasks: Vec<Task>,
-    next_id: usize,
-}
-
-impl TaskList {
-    fn new() -> Self {
-        TaskList {
-            tasks: Vec::new(),
-            next_id: 1,
-        }
-    }
-
-    /// Load task list from file
-    ///
-    /// # Arguments
-    ///
-    /// - `filename` (`&str`)
-    ///
-    /// # Returns
-    ///
-    /// - `Result<Self, Box<dyn std::error::Error>>`
-    ///
-    /// # Errors
-    ///
-    /// Describe possible errors.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use crate::...;
-    ///
-    /// let _ = load_from_file();
-    /// ```
-    fn load_from_file(filename: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        if std::path::Path::new(filename).exists() {
-            let contents = fs::read_to_string(filename)?;
-            let task_list = serde_json::from_str(&contents)?;
-            Ok(task_list)
-        } else {
-            Ok(TaskList::new())
-        }
-    }
-
-    /// Save task list to a file
-    ///
-    /// # Arguments
-    ///
-    /// - `&self` (`undefined`)
-    /// - `filename` (`&str`)
-    ///
-    /// # Returns
-    ///
-    /// - `Result<(), Box<dyn std::error::Error>>` - Describe the return value.
-    ///
-    /// # Errors
-    ///
-    /// Describe possible errors.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use crate::...;
-    ///
-    /// let _ = save_to_file();
-    /// ```
-    fn save_to_file(&self, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let json = serde_json::to_string_pretty(self)?;
-        fs::write(filename, json)?;
-        Ok(())
-    }
-
-    /// Add a task to the task list
-    ///
-    /// # Arguments
-    ///
-    /// - `&mut self` (`undefined`)
-    /// - `description` (`String`) - Description of the task
-    ///
-    /// # Returns
-    ///
-    /// - `Result<(), TaskError>`
-    ///
-    /// # Errors
-    ///
-    /// Describe possible errors.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use crate::...;
-    ///
-    /// let _ = add_task();
-    /// ```
-    fn add_task(&mut self, description: String) -> Result<(), TaskError> {
-        if description.trim().is_empty() {
-            return Err(TaskError::InvalidInput(
-                "Task description cannot be empty".to_string(),
-            ));
-        }
-
-        let task = Task {
-            id: self.next_id,
-            description,
-            completed: false,
-        };
-
-        self.tasks.push(task);
-        self.next_id += 1;
-
-        Ok(())
-    }
-
-    /// Mark a task as completed
-    ///
-    /// # Arguments
-    ///
-    /// - `&mut self` (`undefined`)
-    /// - `id` (`usize`) - Numeric ID
-    ///
-    /// # Returns
-    ///
-    /// - `Result<(), TaskError>`
-    ///
-    /// # Errors
-    ///
-    /// Describe possible errors.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use crate::...;
-    ///
-    /// let _ = complete_task();
-    /// ```
-    fn complete_task(&mut self, id: usize) -> Result<(), TaskError> {
-        match self.tasks.iter_mut().find(|task| task.id == id) {
-            Some(task) => {
-                task.completed = true;
-                Ok(())
-            }
-            None => Err(TaskError::TaskNotFound(id)),
-        }
-    }
-
-    /// List out all tasks
-    ///
-    /// # Arguments
-    ///
-    /// - `&self` (`undefined`)
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use crate::...;
-    ///
-    /// let _ = list_tasks();
-    /// ```
-    fn list_tasks(&self) {
-        for task in &self.tasks {
-            println!(
-                "{}. [{}] - {}",
-                task.id,
-                if task.completed { "x" } else { " " },
-                task.description
-            );
-        }
-    }
-
-    /// List out only completed tasks
-    ///
-    /// # Arguments
-    ///
-    /// - `&self` (`undefined`)
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use crate::...;
-    ///
-    /// let _ = list_completed_tasks();
-    /// ```
-    fn list_completed_tasks(&self) {
-        for task in &self.tasks {
-            if task.completed {
-                println!(
-                    "{}. [{}] - {}",
-                    task.id,
-                    if task.completed { "x" } else { " " },
-                    task.description
-                );
-            }
-        }
-    }
-
-    /// Remove a task by its ID
-    ///
-    /// # Arguments
-    ///
-    /// - `&mut self` (`undefined`)
-    /// - `id` (`usize`)
-    ///
-    /// # Returns
-    ///
-    /// - `Result<(), TaskError>`
-    ///
-    /// # Errors
-    ///
-    /// Describe possible errors.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use crate::...;
-    ///
-    /// let _ = remove_task();
-    /// ```
-    fn remove_task(&mut self, id: usize) -> Result<(), TaskError> {
-        let index = self.tasks.iter().position(|task| task.id == id);
-        match index {
-            Some(i) => {
-                self.tasks.remove(i);
-                Ok(())
-            }
-            None => Err(TaskError::TaskNotFound(id)),
-        }
-    }
-}
-
 fn main() {
     let cli = Cli::parse();
 
@@ -340,34 +153,287 @@ fn main() {
 /// let _ = run_command();
 /// ```
 fn run_command(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
-    let filename = "tasks.json";
-    let mut task_list =
-        TaskList::load_from_file(filename).map_err(|e| TaskError::FileError(e.to_string()))?;
+    if let Commands::Completions { shell } = &cli.command {
+        generate(*shell, &mut Cli::command(), "clap-cli", &mut std::io::stdout());
+        return Ok(());
+    }
+
+    let mut repo: Box<dyn Repository> = match cli.backend {
+        Backend::Json => Box::new(JsonRepository::open("tasks.json")?),
+        Backend::Sqlite => Box::new(SqliteRepository::open(sqlite_data_path()?)?),
+    };
 
     match cli.command {
-        Commands::Add { task } => {
+        Commands::Add {
+            task,
+            after,
+            priority,
+            link,
+            tags,
+            command,
+        } => {
             println!("Adding task: {}", task);
-            task_list.add_task(task)?;
-            task_list.save_to_file(filename)?;
+            repo.insert_task(NewTask {
+                description: task,
+                depends_on: after,
+                priority,
+                link,
+                tags,
+                command,
+            })?;
         }
-        Commands::List => {
+        Commands::List { sort, tag } => {
             println!("Listing all tasks");
-            task_list.list_tasks()
+            let mut tasks = repo.get_tasks()?;
+            if let Some(tag) = &tag {
+                tasks.retain(|task| task.tags.iter().any(|t| t == tag));
+            }
+            sort_tasks(&mut tasks, sort);
+            print_tasks(&tasks);
         }
         Commands::ListCompleted => {
             println!("Listing all completed tasks");
-            task_list.list_completed_tasks();
+            print_completed_tasks(&repo.get_tasks()?);
         }
         Commands::Complete { id } => {
             println!("Completing task with ID: {}", id);
-            task_list.complete_task(id)?;
-            task_list.save_to_file(filename)?;
+            repo.complete_task(id)?;
         }
         Commands::Remove { id } => {
             println!("Removing task with ID: {}", id);
-            task_list.remove_task(2)?;
-            task_list.save_to_file(filename)?;
+            repo.remove_task(id)?;
+        }
+        Commands::Edit {
+            id,
+            description,
+            priority,
+            link,
+            no_link,
+            tags,
+            no_tags,
+            after,
+        } => {
+            println!("Editing task with ID: {}", id);
+
+            // Validate every `--after` edge against the current graph before
+            // persisting anything: a failure partway through must not leave
+            // the description/priority/link/tags changes from the same
+            // invocation committed while the dependency edges are not.
+            if !after.is_empty() {
+                let mut tasks = repo.get_tasks()?;
+                if !tasks.iter().any(|task| task.id == id) {
+                    return Err(Box::new(TaskError::TaskNotFound(id)));
+                }
+                for &dep in &after {
+                    if !tasks.iter().any(|task| task.id == dep) {
+                        return Err(Box::new(TaskError::TaskNotFound(dep)));
+                    }
+                    if creates_cycle(&tasks, id, dep) {
+                        return Err(Box::new(TaskError::DependencyCycle(vec![id, dep])));
+                    }
+                    let task = tasks
+                        .iter_mut()
+                        .find(|task| task.id == id)
+                        .expect("checked above");
+                    if !task.depends_on.contains(&dep) {
+                        task.depends_on.push(dep);
+                    }
+                }
+            }
+
+            repo.edit_task(
+                id,
+                TaskUpdate {
+                    description,
+                    priority,
+                    link: if no_link { Some(None) } else { link.map(Some) },
+                    tags: if no_tags {
+                        Some(Vec::new())
+                    } else if tags.is_empty() {
+                        None
+                    } else {
+                        Some(tags)
+                    },
+                },
+            )?;
+            for dep in after {
+                repo.set_dependency(id, dep)?;
+            }
+        }
+        Commands::Start { id } => {
+            println!("Starting task with ID: {}", id);
+            repo.start_task(id)?;
+        }
+        Commands::Stop => {
+            println!("Stopping the active task");
+            repo.stop_task()?;
+        }
+        Commands::Next => {
+            println!("Listing runnable tasks");
+            print_tasks(&repo.next_tasks()?);
+        }
+        Commands::Completions { .. } => unreachable!("handled above before opening the repository"),
+        Commands::Run => {
+            println!("Running command tasks");
+            run_command_tasks(repo.as_mut())?;
         }
     }
     Ok(())
 }
+
+/// Run all incomplete command-bearing tasks to completion.
+///
+/// Tasks are executed in dependency waves: each wave is every incomplete,
+/// not-yet-failed `command`-bearing task whose `depends_on` are all already
+/// completed, and the tasks within a wave run concurrently via rayon. A task
+/// is marked completed only once its command exits successfully; a task
+/// whose command fails is recorded as failed and never retried, which also
+/// excludes anything depending on it from all later waves (its `depends_on`
+/// can never become fully completed). Waves keep running - independent
+/// tasks and tasks only blocked by tasks that have since succeeded still get
+/// a chance - until a wave comes up empty, at which point every failure
+/// collected across all waves is reported together.
+fn run_command_tasks(repo: &mut dyn Repository) -> Result<(), Box<dyn std::error::Error>> {
+    let mut failures = Vec::new();
+    let mut failed_ids: HashSet<usize> = HashSet::new();
+
+    loop {
+        let tasks = repo.get_tasks()?;
+        let completed: HashSet<usize> = tasks
+            .iter()
+            .filter(|task| task.completed)
+            .map(|task| task.id)
+            .collect();
+
+        let wave: Vec<&Task> = tasks
+            .iter()
+            .filter(|task| {
+                !task.completed
+                    && task.command.is_some()
+                    && !failed_ids.contains(&task.id)
+                    && task.depends_on.iter().all(|dep| completed.contains(dep))
+            })
+            .collect();
+
+        if wave.is_empty() {
+            break;
+        }
+
+        let results: Vec<(usize, Result<String, String>)> = wave
+            .par_iter()
+            .map(|task| {
+                let command = task.command.as_deref().expect("filtered above");
+                let result = std::process::Command::new("sh").arg("-c").arg(command).output();
+                let outcome = match result {
+                    Ok(output) if output.status.success() => {
+                        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+                    }
+                    Ok(output) => Err(format!(
+                        "task {} (`{}`) exited with {}\nstdout: {}\nstderr: {}",
+                        task.id,
+                        command,
+                        output.status,
+                        String::from_utf8_lossy(&output.stdout).trim(),
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )),
+                    Err(e) => Err(format!("task {} (`{}`) failed to run: {}", task.id, command, e)),
+                };
+                (task.id, outcome)
+            })
+            .collect();
+
+        for (id, outcome) in results {
+            match outcome {
+                Ok(stdout) => {
+                    if !stdout.is_empty() {
+                        println!("task {} output:\n{}", id, stdout);
+                    }
+                    if let Err(e) = repo.complete_task(id) {
+                        failed_ids.insert(id);
+                        failures.push(format!("task {} ran but could not be completed: {}", id, e));
+                    }
+                }
+                Err(message) => {
+                    failed_ids.insert(id);
+                    failures.push(message);
+                }
+            }
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(Box::new(TaskError::CommandFailures(failures)))
+    }
+}
+
+/// Path to the SQLite database under the user's data dir
+/// (`<data dir>/clap-cli/tasks.db`).
+fn sqlite_data_path() -> Result<std::path::PathBuf, TaskError> {
+    let dir = dirs::data_dir()
+        .ok_or_else(|| TaskError::FileError("could not determine data directory".to_string()))?
+        .join("clap-cli");
+    Ok(dir.join("tasks.db"))
+}
+
+/// Sort tasks in place per `--sort`. Priority sorts highest-first, with ID
+/// as a deterministic tiebreaker.
+fn sort_tasks(tasks: &mut [Task], sort: SortKey) {
+    match sort {
+        SortKey::Id => tasks.sort_by_key(|task| task.id),
+        SortKey::Priority => tasks.sort_by_key(|task| (std::cmp::Reverse(task.priority), task.id)),
+    }
+}
+
+fn priority_marker(priority: u8) -> String {
+    "!".repeat(priority as usize)
+}
+
+fn print_tasks(tasks: &[Task]) {
+    for task in tasks {
+        println!(
+            "{}. [{}]{} - {} ({}{}){}",
+            task.id,
+            if task.completed { "x" } else { " " },
+            priority_marker(task.priority),
+            task.description,
+            format_duration(task.duration_secs),
+            if task.started_at.is_some() { ", active" } else { "" },
+            match &task.link {
+                Some(link) => format!(" <{}>", link),
+                None => String::new(),
+            }
+        );
+    }
+}
+
+fn print_completed_tasks(tasks: &[Task]) {
+    for task in tasks {
+        if task.completed {
+            println!(
+                "{}. [{}]{} - {} ({})",
+                task.id,
+                if task.completed { "x" } else { " " },
+                priority_marker(task.priority),
+                task.description,
+                format_duration(task.duration_secs)
+            );
+        }
+    }
+}
+
+/// Format accumulated seconds as `1h 2m 3s`, dropping leading zero units.
+fn format_duration(total_secs: u64) -> String {
+    let hours = total_secs / 3600;
+    let minutes = (total_secs % 3600) / 60;
+    let seconds = total_secs % 60;
+
+    if hours > 0 {
+        format!("{}h {}m {}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
@@ -0,0 +1,182 @@
+/**
+ * src/repository.rs
+ */
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::task::{NewTask, Task, TaskError, TaskUpdate};
+
+/// A storage backend for the task list.
+///
+/// Implementors are free to persist tasks however they like (a single JSON
+/// file, a SQLite database, ...) as long as each method leaves storage in a
+/// consistent state by the time it returns. `run_command` only ever talks to
+/// a `Box<dyn Repository>`, so adding a new backend never touches command
+/// handling.
+pub trait Repository {
+    /// Insert a new task and return its assigned ID.
+    fn insert_task(&mut self, new_task: NewTask) -> Result<usize, TaskError>;
+
+    /// Return every task currently in storage.
+    fn get_tasks(&self) -> Result<Vec<Task>, TaskError>;
+
+    /// Mark a task as completed.
+    fn complete_task(&mut self, id: usize) -> Result<(), TaskError>;
+
+    /// Remove a task by ID.
+    fn remove_task(&mut self, id: usize) -> Result<(), TaskError>;
+
+    /// Return the task currently marked active, if any.
+    fn get_current_task_opt(&self) -> Result<Option<Task>, TaskError>;
+
+    /// Mark `id` as the active task, recording its start time. Fails if
+    /// another task is already active.
+    fn start_task(&mut self, id: usize) -> Result<(), TaskError>;
+
+    /// Stop the currently active task, folding elapsed time into its
+    /// `duration_secs`. Fails if no task is active.
+    fn stop_task(&mut self) -> Result<(), TaskError>;
+
+    /// Add `depends_on` as a prerequisite of `id`. Fails if either ID is
+    /// unknown or if the new edge would create a dependency cycle.
+    fn set_dependency(&mut self, id: usize, depends_on: usize) -> Result<(), TaskError>;
+
+    /// Apply a partial update to an existing task. Fails if the task is
+    /// currently active, or if the new description is blank.
+    fn edit_task(&mut self, id: usize, update: TaskUpdate) -> Result<(), TaskError>;
+
+    /// Return incomplete tasks that are runnable right now, i.e. whose
+    /// dependencies are all completed, ordered by ID.
+    ///
+    /// The default implementation runs Kahn's algorithm over the result of
+    /// [`Repository::get_tasks`] to detect cycles, then returns only the
+    /// zero-unsatisfied-dependency frontier rather than the full order, so
+    /// backends only need to override this if they can compute it more
+    /// efficiently in storage.
+    fn next_tasks(&self) -> Result<Vec<Task>, TaskError> {
+        runnable_order(&self.get_tasks()?)
+    }
+}
+
+/// Compute the incomplete tasks that are runnable right now: those with no
+/// unsatisfied (not-yet-completed) prerequisite, ordered by ID, smallest
+/// first, for a deterministic order. Detects cycles with a full run of
+/// Kahn's algorithm and returns `TaskError::DependencyCycle` with the IDs
+/// still stuck if the graph has one, but the happy-path result is only the
+/// first wave of the topological order, not the whole thing — a task whose
+/// prerequisite is incomplete (even if that prerequisite is itself
+/// runnable) must not show up as "next".
+///
+/// A `depends_on` edge can point at an ID that no longer exists (the
+/// prerequisite was `remove`d rather than completed). That's treated as
+/// permanently unsatisfied, never as silently satisfied: such a task, and
+/// anything that in turn depends on it, is excluded from the result rather
+/// than reported as a cycle, since there's nothing actually circular about it.
+pub(crate) fn runnable_order(tasks: &[Task]) -> Result<Vec<Task>, TaskError> {
+    let incomplete: Vec<&Task> = tasks.iter().filter(|t| !t.completed).collect();
+    let all_ids: HashSet<usize> = tasks.iter().map(|t| t.id).collect();
+
+    // A task is permanently blocked if one of its prerequisites was removed
+    // outright (so it can never be completed), or if it depends on another
+    // blocked task. Fixpoint over the (small, per-invocation) task list.
+    let mut blocked: HashSet<usize> = HashSet::new();
+    loop {
+        let mut changed = false;
+        for task in &incomplete {
+            if blocked.contains(&task.id) {
+                continue;
+            }
+            let stuck = task
+                .depends_on
+                .iter()
+                .any(|dep| !all_ids.contains(dep) || blocked.contains(dep));
+            if stuck {
+                blocked.insert(task.id);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let resolvable: Vec<&Task> = incomplete
+        .iter()
+        .filter(|t| !blocked.contains(&t.id))
+        .copied()
+        .collect();
+    let resolvable_ids: HashSet<usize> = resolvable.iter().map(|t| t.id).collect();
+
+    let mut in_degree: HashMap<usize, usize> = HashMap::new();
+    let mut dependents: HashMap<usize, Vec<usize>> = HashMap::new();
+
+    for task in &resolvable {
+        let unsatisfied = task
+            .depends_on
+            .iter()
+            .filter(|dep| resolvable_ids.contains(dep))
+            .count();
+        in_degree.insert(task.id, unsatisfied);
+        for dep in &task.depends_on {
+            if resolvable_ids.contains(dep) {
+                dependents.entry(*dep).or_default().push(task.id);
+            }
+        }
+    }
+
+    let mut queue: BinaryHeap<Reverse<usize>> = in_degree
+        .iter()
+        .filter(|(_, degree)| **degree == 0)
+        .map(|(id, _)| Reverse(*id))
+        .collect();
+
+    let mut wave: Vec<usize> = queue.iter().map(|Reverse(id)| *id).collect();
+    wave.sort_unstable();
+
+    let mut order = Vec::with_capacity(resolvable.len());
+    while let Some(Reverse(id)) = queue.pop() {
+        order.push(id);
+        if let Some(deps) = dependents.get(&id) {
+            for &dependent in deps {
+                let degree = in_degree.get_mut(&dependent).expect("seeded above");
+                *degree -= 1;
+                if *degree == 0 {
+                    queue.push(Reverse(dependent));
+                }
+            }
+        }
+    }
+
+    if order.len() != resolvable.len() {
+        let emitted: HashSet<usize> = order.into_iter().collect();
+        let mut remaining: Vec<usize> = resolvable_ids.difference(&emitted).copied().collect();
+        remaining.sort_unstable();
+        return Err(TaskError::DependencyCycle(remaining));
+    }
+
+    let by_id: HashMap<usize, &Task> = resolvable.iter().map(|t| (t.id, *t)).collect();
+    Ok(wave.into_iter().map(|id| by_id[&id].clone()).collect())
+}
+
+/// Whether adding an edge `id -> depends_on` (i.e. `id` depends on
+/// `depends_on`) would create a cycle, i.e. whether `depends_on` already
+/// (transitively) depends on `id`.
+pub(crate) fn creates_cycle(tasks: &[Task], id: usize, depends_on: usize) -> bool {
+    let by_id: HashMap<usize, &Task> = tasks.iter().map(|t| (t.id, t)).collect();
+    let mut stack = vec![depends_on];
+    let mut seen = HashSet::new();
+
+    while let Some(current) = stack.pop() {
+        if current == id {
+            return true;
+        }
+        if !seen.insert(current) {
+            continue;
+        }
+        if let Some(task) = by_id.get(&current) {
+            stack.extend(task.depends_on.iter().copied());
+        }
+    }
+
+    false
+}
@@ -0,0 +1,142 @@
+/**
+ * src/task.rs
+ */
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Task {
+    pub id: usize,
+    pub description: String,
+    pub completed: bool,
+    /// Unix epoch seconds at which this task was last started, if it is
+    /// currently the active task.
+    #[serde(default)]
+    pub started_at: Option<i64>,
+    /// Total seconds accumulated across all start/stop cycles.
+    #[serde(default)]
+    pub duration_secs: u64,
+    /// IDs of tasks that must be completed before this one is runnable.
+    #[serde(default)]
+    pub depends_on: Vec<usize>,
+    /// Priority from 0 (lowest) to 3 (highest).
+    #[serde(default)]
+    pub priority: u8,
+    /// Optional URL or path associated with the task.
+    #[serde(default)]
+    pub link: Option<String>,
+    /// Free-form labels for filtering.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Shell command this task runs instead of just holding text.
+    #[serde(default)]
+    pub command: Option<String>,
+}
+
+/// Fields needed to insert a new task.
+#[derive(Debug, Default)]
+pub struct NewTask {
+    pub description: String,
+    pub depends_on: Vec<usize>,
+    pub priority: u8,
+    pub link: Option<String>,
+    pub tags: Vec<String>,
+    pub command: Option<String>,
+}
+
+/// A partial update to apply to an existing task.
+///
+/// `link` uses `Option<Option<String>>` so "leave unchanged" (`None`) and
+/// "clear the link" (`Some(None)`) are distinguishable from "set a new
+/// link" (`Some(Some(url))`).
+#[derive(Debug, Default)]
+pub struct TaskUpdate {
+    pub description: Option<String>,
+    pub priority: Option<u8>,
+    pub link: Option<Option<String>>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// Reject blank descriptions; shared by `insert_task` and `edit_task` so the
+/// rule only lives in one place.
+pub fn validate_description(description: &str) -> Result<(), TaskError> {
+    if description.trim().is_empty() {
+        Err(TaskError::InvalidInput(
+            "Task description cannot be empty".to_string(),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Reject empty tags; shared by `insert_task` and `edit_task` on both
+/// backends so the rule doesn't depend on how a given backend happens to
+/// encode tags on disk.
+pub fn validate_tags(tags: &[String]) -> Result<(), TaskError> {
+    for tag in tags {
+        if tag.is_empty() {
+            return Err(TaskError::InvalidInput(
+                "Tags cannot be empty".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+pub enum TaskError {
+    FileError(String),
+    TaskNotFound(usize),
+    InvalidInput(String),
+    /// A task is already active; holds its ID.
+    TaskAlreadyActive(usize),
+    /// The target task is the currently active one and can't be modified
+    /// (completed, edited, ...) until it's stopped.
+    TaskIsActive(usize),
+    /// `stop` was called but no task is currently active.
+    NoActiveTask,
+    /// The target task is already completed and can't be started again;
+    /// holds its ID.
+    TaskAlreadyCompleted(usize),
+    /// Computing a runnable order hit a cycle; holds the IDs still stuck.
+    DependencyCycle(Vec<usize>),
+    /// One or more command-bearing tasks failed during `run`; holds one
+    /// message per failure.
+    CommandFailures(Vec<String>),
+}
+
+impl fmt::Display for TaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskError::FileError(msg) => write!(f, "File Error: {}", msg),
+            TaskError::TaskNotFound(id) => write!(f, "Task with ID {} not found", id),
+            TaskError::InvalidInput(msg) => write!(f, "Invalid Input: {}", msg),
+            TaskError::TaskAlreadyActive(id) => {
+                write!(f, "Task {} is already active; stop it first", id)
+            }
+            TaskError::TaskIsActive(id) => {
+                write!(f, "Task {} is currently active and can't be modified", id)
+            }
+            TaskError::NoActiveTask => write!(f, "No task is currently active"),
+            TaskError::TaskAlreadyCompleted(id) => {
+                write!(f, "Task {} is already completed and can't be started", id)
+            }
+            TaskError::DependencyCycle(ids) => {
+                write!(f, "Dependency cycle detected among tasks: {:?}", ids)
+            }
+            TaskError::CommandFailures(messages) => {
+                write!(f, "{} task command(s) failed:\n{}", messages.len(), messages.join("\n"))
+            }
+        }
+    }
+}
+
+impl std::error::Error for TaskError {}
+
+/// Current unix epoch time in seconds, used to stamp task start/stop times.
+pub fn now_unix() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("system clock is before the unix epoch")
+        .as_secs() as i64
+}
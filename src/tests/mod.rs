@@ -1,53 +1,479 @@
-#[cfg(test)]
-mod tests {
-    use crate::TaskError;
-    use crate::TaskList;
-
-    #[test]
-    fn empty_task_rejected_test() {
-        let mut task_list: TaskList = TaskList::new();
-        let result: Result<(), TaskError> = task_list.add_task("      ".to_string());
-        assert!(result.is_err());
+#![cfg(test)]
+
+use std::path::PathBuf;
+
+use crate::backends::json::JsonRepository;
+use crate::backends::sqlite::SqliteRepository;
+use crate::repository::Repository;
+use crate::task::{NewTask, Task, TaskError, TaskUpdate};
+
+fn blank_task(id: usize, priority: u8) -> Task {
+    Task {
+        id,
+        description: String::new(),
+        completed: false,
+        started_at: None,
+        duration_secs: 0,
+        depends_on: Vec::new(),
+        priority,
+        link: None,
+        tags: Vec::new(),
+        command: None,
     }
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("clap_cli_test_{}_{}.json", name, std::process::id()))
+}
 
-    #[test]
-    /// Kitchen sink test - add, list, etc.
-    fn kitchen_sink_test() {
-        let mut task_list = TaskList::new();
-        let task1: &str = "Task 1";
-        let task2: &str = "Task 2";
-        let task3: &str = "Task 3";
-
-        // add task 1
-        let result: Result<(), TaskError> = task_list.add_task(task1.to_string());
-        assert!(result.is_ok());
-        assert_eq!(task_list.tasks.len(), 1);
-        assert_eq!(task_list.tasks[0].description, task1);
-
-        // add task 2
-        let result: Result<(), TaskError> = task_list.add_task(task2.to_string());
-        assert!(result.is_ok());
-        assert_eq!(task_list.tasks.len(), 2);
-        assert_eq!(task_list.tasks[1].description, task2);
-
-        // add task 3
-        let result: Result<(), TaskError> = task_list.add_task(task3.to_string());
-        assert!(result.is_ok());
-        assert_eq!(task_list.tasks.len(), 3);
-        assert_eq!(task_list.tasks[2].description, task3);
-
-        // remove task 2
-        let result: Result<(), TaskError> = task_list.remove_task(2);
-        assert!(result.is_ok());
-        assert_eq!(task_list.tasks.len(), 2);
-
-        // mark task 3 as completed
-        let result: Result<(), TaskError> = task_list.complete_task(3);
-        assert!(result.is_ok());
-        assert_eq!(task_list.tasks[1].completed, true);
-
-        // mark a non-existent task as completed
-        let result: Result<(), TaskError> = task_list.complete_task(100);
-        assert!(result.is_err());
+fn temp_db_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("clap_cli_test_{}_{}.db", name, std::process::id()))
+}
+
+fn new_task(description: &str) -> NewTask {
+    NewTask {
+        description: description.to_string(),
+        ..Default::default()
     }
 }
+
+#[test]
+fn empty_task_rejected_test() {
+    let path = temp_path("empty_task_rejected");
+    let mut repo = JsonRepository::open(&path).unwrap();
+    let result: Result<usize, TaskError> = repo.insert_task(new_task("      "));
+    assert!(result.is_err());
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+/// Kitchen sink test - add, list, etc.
+fn kitchen_sink_test() {
+    let path = temp_path("kitchen_sink");
+    let mut repo = JsonRepository::open(&path).unwrap();
+    let task1: &str = "Task 1";
+    let task2: &str = "Task 2";
+    let task3: &str = "Task 3";
+
+    // add task 1
+    let result: Result<usize, TaskError> = repo.insert_task(new_task(task1));
+    assert!(result.is_ok());
+    assert_eq!(repo.get_tasks().unwrap().len(), 1);
+    assert_eq!(repo.get_tasks().unwrap()[0].description, task1);
+
+    // add task 2
+    let result: Result<usize, TaskError> = repo.insert_task(new_task(task2));
+    assert!(result.is_ok());
+    assert_eq!(repo.get_tasks().unwrap().len(), 2);
+    assert_eq!(repo.get_tasks().unwrap()[1].description, task2);
+
+    // add task 3
+    let result: Result<usize, TaskError> = repo.insert_task(new_task(task3));
+    assert!(result.is_ok());
+    assert_eq!(repo.get_tasks().unwrap().len(), 3);
+    assert_eq!(repo.get_tasks().unwrap()[2].description, task3);
+
+    // remove task 2
+    let result: Result<(), TaskError> = repo.remove_task(2);
+    assert!(result.is_ok());
+    assert_eq!(repo.get_tasks().unwrap().len(), 2);
+
+    // mark task 3 as completed
+    let result: Result<(), TaskError> = repo.complete_task(3);
+    assert!(result.is_ok());
+    assert!(repo.get_tasks().unwrap()[1].completed);
+
+    // mark a non-existent task as completed
+    let result: Result<(), TaskError> = repo.complete_task(100);
+    assert!(result.is_err());
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn start_stop_tracks_duration_and_rejects_completed_test() {
+    let path = temp_path("start_stop");
+    let mut repo = JsonRepository::open(&path).unwrap();
+    let id = repo.insert_task(new_task("Track me")).unwrap();
+
+    repo.start_task(id).unwrap();
+    assert!(repo.get_tasks().unwrap()[0].started_at.is_some());
+
+    // starting a second task while one is active is rejected
+    let other = repo.insert_task(new_task("Other")).unwrap();
+    assert!(matches!(
+        repo.start_task(other),
+        Err(TaskError::TaskAlreadyActive(active_id)) if active_id == id
+    ));
+
+    repo.stop_task().unwrap();
+    let task = repo
+        .get_tasks()
+        .unwrap()
+        .into_iter()
+        .find(|task| task.id == id)
+        .unwrap();
+    assert!(task.started_at.is_none());
+
+    // a completed task can't be started again
+    repo.complete_task(id).unwrap();
+    assert!(matches!(
+        repo.start_task(id),
+        Err(TaskError::TaskAlreadyCompleted(completed_id)) if completed_id == id
+    ));
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn next_tasks_orders_by_id_and_detects_cycles_test() {
+    let path = temp_path("next_tasks");
+    let mut repo = JsonRepository::open(&path).unwrap();
+
+    let a = repo.insert_task(new_task("A")).unwrap();
+    let b = repo.insert_task(new_task("B")).unwrap();
+    let c = repo
+        .insert_task(NewTask {
+            description: "C".to_string(),
+            depends_on: vec![a, b],
+            ..Default::default()
+        })
+        .unwrap();
+
+    // a and b are both runnable with no deps; c depends on both and is not
+    // runnable yet, so it must be excluded from the frontier entirely.
+    let runnable: Vec<usize> = repo.next_tasks().unwrap().iter().map(|task| task.id).collect();
+    assert_eq!(runnable, vec![a, b]);
+
+    // c already (transitively) depends on a, so making a depend on c would
+    // close a cycle and must be rejected.
+    assert!(matches!(
+        repo.set_dependency(a, c),
+        Err(TaskError::DependencyCycle(_))
+    ));
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn set_dependency_is_idempotent_on_a_repeated_edge_test() {
+    // re-adding the same `--after` edge must succeed as a no-op on both
+    // backends, not silently duplicate the edge (json) or leak a backend
+    // storage error for a UNIQUE constraint (sqlite).
+    let json_path = temp_path("dup_dependency");
+    let mut json_repo = JsonRepository::open(&json_path).unwrap();
+    let a = json_repo.insert_task(new_task("A")).unwrap();
+    let b = json_repo.insert_task(new_task("B")).unwrap();
+    json_repo.set_dependency(b, a).unwrap();
+    json_repo.set_dependency(b, a).unwrap();
+    let tasks = json_repo.get_tasks().unwrap();
+    assert_eq!(tasks.iter().find(|t| t.id == b).unwrap().depends_on, vec![a]);
+    let _ = std::fs::remove_file(&json_path);
+
+    let db_path = temp_db_path("dup_dependency");
+    let mut sqlite_repo = SqliteRepository::open(&db_path).unwrap();
+    let a = sqlite_repo.insert_task(new_task("A")).unwrap();
+    let b = sqlite_repo.insert_task(new_task("B")).unwrap();
+    sqlite_repo.set_dependency(b, a).unwrap();
+    sqlite_repo.set_dependency(b, a).unwrap();
+    let tasks = sqlite_repo.get_tasks().unwrap();
+    assert_eq!(tasks.iter().find(|t| t.id == b).unwrap().depends_on, vec![a]);
+    let _ = std::fs::remove_file(&db_path);
+}
+
+#[test]
+fn next_tasks_excludes_tasks_with_an_incomplete_prerequisite_test() {
+    let path = temp_path("next_tasks_incomplete_prereq");
+    let mut repo = JsonRepository::open(&path).unwrap();
+
+    let a = repo.insert_task(new_task("A")).unwrap();
+    let b = repo
+        .insert_task(NewTask {
+            description: "B".to_string(),
+            depends_on: vec![a],
+            ..Default::default()
+        })
+        .unwrap();
+
+    // b depends on a, which is still incomplete, so only a is runnable.
+    let runnable: Vec<usize> = repo.next_tasks().unwrap().iter().map(|task| task.id).collect();
+    assert_eq!(runnable, vec![a]);
+
+    // Once a is completed, b's only prerequisite is satisfied and it joins
+    // the runnable frontier.
+    repo.complete_task(a).unwrap();
+    let runnable: Vec<usize> = repo.next_tasks().unwrap().iter().map(|task| task.id).collect();
+    assert_eq!(runnable, vec![b]);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn next_tasks_excludes_tasks_whose_prerequisite_was_removed_test() {
+    let path = temp_path("next_tasks_removed_prereq");
+    let mut repo = JsonRepository::open(&path).unwrap();
+
+    let prereq = repo.insert_task(new_task("prereq")).unwrap();
+    repo.insert_task(NewTask {
+        description: "dependent".to_string(),
+        depends_on: vec![prereq],
+        ..Default::default()
+    })
+    .unwrap();
+
+    repo.remove_task(prereq).unwrap();
+
+    // prereq was removed, not completed, so dependent must never show up as
+    // runnable: a deleted ID is permanently unsatisfied, not silently satisfied.
+    let runnable: Vec<usize> = repo.next_tasks().unwrap().iter().map(|task| task.id).collect();
+    assert!(runnable.is_empty());
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn edit_distinguishes_noop_from_explicit_clear_test() {
+    let path = temp_path("edit_noop_vs_clear");
+    let mut repo = JsonRepository::open(&path).unwrap();
+
+    let id = repo
+        .insert_task(NewTask {
+            description: "Task".to_string(),
+            link: Some("https://example.com".to_string()),
+            priority: 1,
+            ..Default::default()
+        })
+        .unwrap();
+
+    // omitting `link` (None) in the update leaves it unchanged
+    repo.edit_task(
+        id,
+        TaskUpdate {
+            priority: Some(2),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        repo.get_tasks().unwrap()[0].link,
+        Some("https://example.com".to_string())
+    );
+    assert_eq!(repo.get_tasks().unwrap()[0].priority, 2);
+
+    // Some(None) explicitly clears it
+    repo.edit_task(
+        id,
+        TaskUpdate {
+            link: Some(None),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(repo.get_tasks().unwrap()[0].link, None);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn edit_clears_tags_with_an_explicit_empty_vec_test() {
+    let path = temp_path("edit_clears_tags");
+    let mut repo = JsonRepository::open(&path).unwrap();
+
+    let id = repo
+        .insert_task(NewTask {
+            description: "Task".to_string(),
+            tags: vec!["infra".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+
+    // omitting tags (None) leaves them unchanged
+    repo.edit_task(id, TaskUpdate::default()).unwrap();
+    assert_eq!(repo.get_tasks().unwrap()[0].tags, vec!["infra".to_string()]);
+
+    // Some(vec![]) - what `--no-tags` sends - clears them
+    repo.edit_task(
+        id,
+        TaskUpdate {
+            tags: Some(Vec::new()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(repo.get_tasks().unwrap()[0].tags.is_empty());
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn run_command_tasks_continues_after_failure_and_aggregates_test() {
+    let path = temp_path("run_command_tasks");
+    let mut repo = JsonRepository::open(&path).unwrap();
+
+    let failing = repo
+        .insert_task(NewTask {
+            description: "failing".to_string(),
+            command: Some("exit 1".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+    let succeeding = repo
+        .insert_task(NewTask {
+            description: "succeeding".to_string(),
+            command: Some("true".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+    let blocked = repo
+        .insert_task(NewTask {
+            description: "blocked".to_string(),
+            depends_on: vec![failing],
+            command: Some("true".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+    let unblocked = repo
+        .insert_task(NewTask {
+            description: "unblocked".to_string(),
+            depends_on: vec![succeeding],
+            command: Some("true".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let result = crate::run_command_tasks(&mut repo);
+    assert!(result.is_err());
+
+    let tasks = repo.get_tasks().unwrap();
+    let is_completed = |id: usize| tasks.iter().find(|task| task.id == id).unwrap().completed;
+    // the failed task's dependent never gets a chance, but a sibling
+    // wave and anything depending only on the succeeding task still runs.
+    assert!(!is_completed(failing));
+    assert!(is_completed(succeeding));
+    assert!(!is_completed(blocked));
+    assert!(is_completed(unblocked));
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn run_command_tasks_reports_active_task_as_a_failure_without_dropping_siblings_test() {
+    let path = temp_path("run_command_tasks_active");
+    let mut repo = JsonRepository::open(&path).unwrap();
+
+    let active = repo
+        .insert_task(NewTask {
+            description: "active".to_string(),
+            command: Some("true".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+    let sibling = repo
+        .insert_task(NewTask {
+            description: "sibling".to_string(),
+            command: Some("true".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+    repo.start_task(active).unwrap();
+
+    let result = crate::run_command_tasks(&mut repo);
+    assert!(result.is_err());
+
+    let tasks = repo.get_tasks().unwrap();
+    let is_completed = |id: usize| tasks.iter().find(|task| task.id == id).unwrap().completed;
+    // the active task's own command ran but it can't be completed while
+    // tracked; its sibling in the same wave must still finish.
+    assert!(!is_completed(active));
+    assert!(is_completed(sibling));
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn sqlite_repository_round_trip_test() {
+    let path = temp_db_path("sqlite_round_trip");
+    let mut repo = SqliteRepository::open(&path).unwrap();
+
+    let first = repo
+        .insert_task(NewTask {
+            description: "first".to_string(),
+            tags: vec!["infra".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+    let second = repo
+        .insert_task(NewTask {
+            description: "second".to_string(),
+            depends_on: vec![first],
+            priority: 2,
+            ..Default::default()
+        })
+        .unwrap();
+
+    let tasks = repo.get_tasks().unwrap();
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[0].tags, vec!["infra".to_string()]);
+    assert_eq!(tasks[1].depends_on, vec![first]);
+
+    repo.edit_task(
+        second,
+        TaskUpdate {
+            tags: Some(vec!["urgent".to_string()]),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(repo.get_tasks().unwrap()[1].tags, vec!["urgent".to_string()]);
+
+    repo.complete_task(first).unwrap();
+    repo.remove_task(second).unwrap();
+    assert_eq!(repo.get_tasks().unwrap().len(), 1);
+
+    // reopening re-runs migrate(); against an already-applied schema that
+    // must be a no-op, and the persisted data must survive the round trip.
+    let reopened = SqliteRepository::open(&path).unwrap();
+    assert_eq!(reopened.get_tasks().unwrap().len(), 1);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn sqlite_tags_reject_empty_and_round_trip_commas_like_json_test() {
+    let path = temp_db_path("sqlite_tags_commas");
+    let mut repo = SqliteRepository::open(&path).unwrap();
+
+    let err = repo
+        .insert_task(NewTask {
+            description: "empty tag".to_string(),
+            tags: vec!["".to_string()],
+            ..Default::default()
+        })
+        .unwrap_err();
+    assert!(matches!(err, TaskError::InvalidInput(_)));
+
+    // a tag containing a comma round-trips through the JSON-encoded column
+    // just like any other string; the JSON encoding has no need to reject it.
+    let id = repo
+        .insert_task(NewTask {
+            description: "has a comma tag".to_string(),
+            tags: vec!["a,b".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+    let tasks = repo.get_tasks().unwrap();
+    assert_eq!(tasks[0].id, id);
+    assert_eq!(tasks[0].tags, vec!["a,b".to_string()]);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn sort_tasks_orders_by_priority_then_id_test() {
+    let mut tasks = vec![blank_task(3, 1), blank_task(1, 2), blank_task(2, 2)];
+    crate::sort_tasks(&mut tasks, crate::SortKey::Priority);
+    assert_eq!(
+        tasks.iter().map(|task| task.id).collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+}